@@ -0,0 +1,78 @@
+//! Start-on-login support. The app fronts a long-running `aetherd` core,
+//! so users expect it to come back up after a reboot without manually
+//! opening the window.
+
+use std::fs;
+use std::path::PathBuf;
+
+use auto_launch::AutoLaunch;
+use tauri::AppHandle;
+
+const PREF_FILE_NAME: &str = "autostart.pref";
+
+/// Wraps OS-level login-item registration plus a small preference file so
+/// the toggle survives even if the OS registration and our record of it
+/// ever drift apart (e.g. after a manual reinstall).
+pub struct AutoStart {
+    auto_launch: AutoLaunch,
+    pref_path: PathBuf,
+}
+
+impl AutoStart {
+    /// Fails only if the current executable's path can't be resolved
+    /// (deleted/moved binary, some sandboxed setups); callers should treat
+    /// that as "skip autostart" rather than a fatal startup error, since
+    /// none of the rest of the app depends on it.
+    pub fn new(app: &AppHandle) -> std::io::Result<Self> {
+        let exe = std::env::current_exe()?.to_string_lossy().to_string();
+        let auto_launch = AutoLaunch::new(&app.package_info().name, &exe, &[] as &[&str]);
+
+        let pref_path = app
+            .path_resolver()
+            .app_config_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join(PREF_FILE_NAME);
+
+        Ok(AutoStart {
+            auto_launch,
+            pref_path,
+        })
+    }
+
+    /// Whether the OS currently launches the app at login.
+    pub fn is_enabled(&self) -> bool {
+        self.auto_launch.is_enabled().unwrap_or(false)
+    }
+
+    /// Registers or unregisters the login item and persists the choice.
+    pub fn set_enabled(&self, enabled: bool) -> Result<(), String> {
+        let result = if enabled {
+            self.auto_launch.enable()
+        } else {
+            self.auto_launch.disable()
+        };
+        result.map_err(|err| err.to_string())?;
+        self.persist(enabled);
+        Ok(())
+    }
+
+    fn persist(&self, enabled: bool) {
+        if let Some(parent) = self.pref_path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let _ = fs::write(&self.pref_path, if enabled { "1" } else { "0" });
+    }
+
+    /// Re-applies the last persisted preference if it disagrees with the
+    /// live OS registration, so a preference set before e.g. a reinstall
+    /// still takes effect.
+    pub fn sync_on_startup(&self) {
+        let Ok(contents) = fs::read_to_string(&self.pref_path) else {
+            return;
+        };
+        let persisted = contents.trim() == "1";
+        if persisted != self.is_enabled() {
+            let _ = self.set_enabled(persisted);
+        }
+    }
+}
@@ -0,0 +1,373 @@
+//! Supervises the embedded `aetherd` child process: watches it for unexpected
+//! exits and restarts it with exponential backoff so the UI always has a
+//! live backend to talk to.
+
+use std::net::{SocketAddr, TcpListener};
+use std::process::Child;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+/// Where `CoreSupervisor` reports status changes. Exists so tests can drive
+/// the watcher without a real Tauri `AppHandle`; production code just uses
+/// the `AppHandle` impl below.
+pub trait StatusSink: Send + Sync + 'static {
+    fn emit_status(&self, status: CoreStatus);
+}
+
+impl StatusSink for AppHandle {
+    fn emit_status(&self, status: CoreStatus) {
+        let _ = self.emit_all("core-status", status);
+    }
+}
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Env var letting power users pin `aetherd`'s API port instead of letting
+/// the OS assign one.
+const PORT_OVERRIDE_ENV: &str = "AETHER_API_PORT";
+
+/// The address the embedded `aetherd` is listening on, managed as Tauri
+/// state so `core_api_addr` can report it to the webview.
+pub struct CoreAddr(pub SocketAddr);
+
+/// Picks the address to pass to `aetherd --api`: the `AETHER_API_PORT`
+/// override if set, otherwise a free port assigned by the OS.
+pub fn pick_api_addr() -> std::io::Result<SocketAddr> {
+    if let Some(port) = std::env::var(PORT_OVERRIDE_ENV)
+        .ok()
+        .and_then(|p| p.parse::<u16>().ok())
+    {
+        return Ok(SocketAddr::from(([127, 0, 0, 1], port)));
+    }
+
+    let listener = TcpListener::bind(("127.0.0.1", 0))?;
+    let addr = listener.local_addr()?;
+    // Dropping releases the port for aetherd to bind; there's an inherent
+    // (tiny) race if something else grabs it first, same as any "find a
+    // free port" scheme.
+    drop(listener);
+    Ok(addr)
+}
+
+/// Current health of the supervised `aetherd` process, mirrored to the
+/// frontend via the `core-status` event.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum CoreStatus {
+    Running,
+    Restarting,
+    Failed { exit_code: Option<i32> },
+}
+
+/// The supervised child's lifecycle slot. Kept as its own enum (rather than
+/// overloading `Option<Child>`) so "exited, about to retry" and "taken for
+/// a deliberate shutdown" can't be confused with each other.
+enum Slot {
+    Alive(Child),
+    /// Exited unexpectedly; the watcher is between respawn attempts.
+    Respawning,
+    /// A shutdown path has taken ownership of (or given up waiting for) the
+    /// child; the watcher must stop restarting it.
+    ShutDown,
+}
+
+/// Owns the `aetherd` `Child` and knows how to respawn it. Managed as Tauri
+/// state so commands and the tray/window handlers can all reach it.
+pub struct CoreSupervisor<S: StatusSink = AppHandle> {
+    slot: Mutex<Slot>,
+    status: Mutex<CoreStatus>,
+    shutting_down: AtomicBool,
+    respawn: Box<dyn Fn() -> std::io::Result<Child> + Send + Sync>,
+    sink: S,
+}
+
+impl<S: StatusSink> CoreSupervisor<S> {
+    /// Spawns `aetherd` for the first time and starts the background
+    /// watcher thread that keeps it alive.
+    pub fn spawn(
+        sink: S,
+        respawn: impl Fn() -> std::io::Result<Child> + Send + Sync + 'static,
+    ) -> std::io::Result<Arc<Self>> {
+        let child = respawn()?;
+        let supervisor = Arc::new(CoreSupervisor {
+            slot: Mutex::new(Slot::Alive(child)),
+            status: Mutex::new(CoreStatus::Running),
+            shutting_down: AtomicBool::new(false),
+            respawn: Box::new(respawn),
+            sink,
+        });
+
+        let watcher = supervisor.clone();
+        thread::spawn(move || watcher.watch());
+
+        Ok(supervisor)
+    }
+
+    fn set_status(&self, status: CoreStatus) {
+        *self.status.lock().unwrap() = status;
+        self.sink.emit_status(status);
+    }
+
+    pub fn status(&self) -> CoreStatus {
+        *self.status.lock().unwrap()
+    }
+
+    /// Requests that the watcher stop restarting `aetherd`. Call before
+    /// killing the child on a deliberate shutdown path.
+    pub fn begin_shutdown(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    /// Takes the current child out for a caller that wants to terminate it
+    /// directly (tray quit, window close), marking the slot as shut down so
+    /// the watcher won't try to respawn into it. Returns `None` if there is
+    /// no live child right now (e.g. a respawn is in flight) — the watcher
+    /// will still see the shut-down marker and stop on its own.
+    pub fn take_child(&self) -> Option<Child> {
+        match std::mem::replace(&mut *self.slot.lock().unwrap(), Slot::ShutDown) {
+            Slot::Alive(child) => Some(child),
+            Slot::Respawning | Slot::ShutDown => None,
+        }
+    }
+
+    fn watch(self: Arc<Self>) {
+        let mut backoff = INITIAL_BACKOFF;
+        let mut last_spawned_at = Instant::now();
+
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            if self.shutting_down.load(Ordering::SeqCst) {
+                return;
+            }
+
+            let exit_status = {
+                let mut slot = self.slot.lock().unwrap();
+                match &mut *slot {
+                    Slot::Alive(child) => match child.try_wait() {
+                        Ok(Some(status)) => {
+                            *slot = Slot::Respawning;
+                            Some(status)
+                        }
+                        Ok(None) => None,
+                        Err(_) => None,
+                    },
+                    // A shutdown path already took (or gave up on) the
+                    // child; stop supervising.
+                    Slot::ShutDown => return,
+                    Slot::Respawning => None,
+                }
+            };
+
+            let Some(exit_status) = exit_status else {
+                continue;
+            };
+
+            // A run that stayed up past the stability threshold earns back
+            // the short initial backoff instead of carrying over the
+            // previous, possibly-large, delay.
+            if last_spawned_at.elapsed() >= STABLE_AFTER {
+                backoff = INITIAL_BACKOFF;
+            }
+
+            self.set_status(CoreStatus::Failed {
+                exit_code: exit_status.code(),
+            });
+
+            // Keep retrying the respawn with exponential backoff until it
+            // succeeds or a shutdown is requested mid-retry; a respawn
+            // failure must not be mistaken for a deliberate shutdown.
+            loop {
+                if self.shutting_down.load(Ordering::SeqCst) {
+                    return;
+                }
+
+                self.set_status(CoreStatus::Restarting);
+                thread::sleep(backoff);
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+
+                // Re-check right before spawning: a shutdown could have
+                // been requested while we were asleep.
+                if self.shutting_down.load(Ordering::SeqCst) {
+                    return;
+                }
+
+                match (self.respawn)() {
+                    Ok(mut child) => {
+                        // And once more right after, under the slot lock: a
+                        // shutdown could have raced with the spawn itself, in
+                        // which case this freshly started child would
+                        // otherwise be left untracked and outlive the app.
+                        let mut slot = self.slot.lock().unwrap();
+                        if self.shutting_down.load(Ordering::SeqCst)
+                            || matches!(*slot, Slot::ShutDown)
+                        {
+                            drop(slot);
+                            let _ = child.kill();
+                            return;
+                        }
+                        *slot = Slot::Alive(child);
+                        drop(slot);
+                        last_spawned_at = Instant::now();
+                        self.set_status(CoreStatus::Running);
+                        break;
+                    }
+                    Err(_) => {
+                        self.set_status(CoreStatus::Failed { exit_code: None });
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Reports the current state of the supervised `aetherd` process.
+#[tauri::command]
+pub fn core_status(supervisor: tauri::State<Arc<CoreSupervisor>>) -> CoreStatus {
+    supervisor.status()
+}
+
+/// Reports the address the webview should use to reach `aetherd`.
+#[tauri::command]
+pub fn core_api_addr(addr: tauri::State<CoreAddr>) -> String {
+    addr.0.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+    use std::sync::Mutex as StdMutex;
+
+    // `pick_api_addr` reads a process-wide env var, so serialize the tests
+    // that touch it to avoid one test's override leaking into another.
+    static ENV_GUARD: StdMutex<()> = StdMutex::new(());
+
+    #[test]
+    fn pick_api_addr_defaults_to_an_os_assigned_port() {
+        let _guard = ENV_GUARD.lock().unwrap();
+        std::env::remove_var(PORT_OVERRIDE_ENV);
+
+        let addr = pick_api_addr().expect("should find a free port");
+
+        assert_eq!(addr.ip(), Ipv4Addr::LOCALHOST);
+        assert_ne!(addr.port(), 0);
+    }
+
+    #[test]
+    fn pick_api_addr_honors_the_override_env_var() {
+        let _guard = ENV_GUARD.lock().unwrap();
+        std::env::set_var(PORT_OVERRIDE_ENV, "54321");
+
+        let addr = pick_api_addr().expect("override should parse");
+
+        std::env::remove_var(PORT_OVERRIDE_ENV);
+        assert_eq!(addr, SocketAddr::from(([127, 0, 0, 1], 54321)));
+    }
+
+    #[test]
+    fn pick_api_addr_falls_back_to_os_assigned_on_invalid_override() {
+        let _guard = ENV_GUARD.lock().unwrap();
+        std::env::set_var(PORT_OVERRIDE_ENV, "not-a-port");
+
+        let addr = pick_api_addr().expect("should fall back to an OS-assigned port");
+
+        std::env::remove_var(PORT_OVERRIDE_ENV);
+        assert_ne!(addr.port(), 0);
+    }
+
+    // Records every status the watcher reports, standing in for the real
+    // `AppHandle` sink so these tests don't need a live Tauri app.
+    struct RecordingSink {
+        events: StdMutex<Vec<CoreStatus>>,
+    }
+
+    impl RecordingSink {
+        fn new() -> Arc<Self> {
+            Arc::new(RecordingSink {
+                events: StdMutex::new(Vec::new()),
+            })
+        }
+
+        fn snapshot(&self) -> Vec<CoreStatus> {
+            self.events.lock().unwrap().clone()
+        }
+    }
+
+    impl StatusSink for Arc<RecordingSink> {
+        fn emit_status(&self, status: CoreStatus) {
+            self.events.lock().unwrap().push(status);
+        }
+    }
+
+    #[test]
+    fn supervisor_keeps_respawning_a_crashing_child_until_shutdown() {
+        use std::process::Command;
+        use std::sync::atomic::AtomicUsize;
+
+        let sink = RecordingSink::new();
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let attempts_for_respawn = attempts.clone();
+
+        let supervisor = CoreSupervisor::spawn(sink.clone(), move || {
+            attempts_for_respawn.fetch_add(1, Ordering::SeqCst);
+            Command::new("sh").arg("-c").arg("exit 1").spawn()
+        })
+        .expect("initial spawn should succeed");
+
+        // Give the watcher time to notice the first exit and retry at
+        // least once (POLL_INTERVAL to notice + INITIAL_BACKOFF to retry,
+        // plus slack for scheduling jitter).
+        thread::sleep(POLL_INTERVAL + INITIAL_BACKOFF + Duration::from_millis(500));
+        let attempts_before_shutdown = attempts.load(Ordering::SeqCst);
+        assert!(
+            attempts_before_shutdown >= 2,
+            "expected at least one respawn beyond the initial spawn, got {attempts_before_shutdown}"
+        );
+
+        // This is exactly the race the chunk0-1 fix commit addressed: a
+        // shutdown landing mid-retry must stick, not get overwritten by a
+        // respawn already in flight.
+        supervisor.begin_shutdown();
+        let _ = supervisor.take_child();
+
+        thread::sleep(Duration::from_millis(750));
+        let attempts_after_shutdown = attempts.load(Ordering::SeqCst);
+
+        thread::sleep(Duration::from_millis(750));
+        assert_eq!(
+            attempts.load(Ordering::SeqCst),
+            attempts_after_shutdown,
+            "watcher kept respawning after begin_shutdown"
+        );
+    }
+
+    #[test]
+    fn supervisor_reports_restarting_status_after_a_crash() {
+        use std::process::Command;
+
+        let sink = RecordingSink::new();
+        let supervisor = CoreSupervisor::spawn(sink.clone(), || {
+            Command::new("sh").arg("-c").arg("exit 1").spawn()
+        })
+        .expect("initial spawn should succeed");
+
+        thread::sleep(POLL_INTERVAL + INITIAL_BACKOFF + Duration::from_millis(500));
+
+        supervisor.begin_shutdown();
+        let _ = supervisor.take_child();
+
+        let events = sink.snapshot();
+        assert!(
+            events.iter().any(|s| matches!(s, CoreStatus::Restarting)),
+            "expected a Restarting status among {events:?}"
+        );
+    }
+}
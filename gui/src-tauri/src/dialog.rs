@@ -0,0 +1,65 @@
+//! Native error dialog for fatal startup failures.
+//!
+//! A `windows_subsystem = "windows"` release build has no console, so a
+//! panic during `setup` just disappears. This surfaces the failure as a
+//! blocking native dialog instead, then exits cleanly.
+
+use std::thread;
+
+/// Shows a blocking native error dialog with `title`/`message`, waits for
+/// the user to dismiss it, then exits the process with a non-zero code.
+///
+/// `fatal` is always called synchronously from inside Tauri's `setup()`,
+/// i.e. on the thread that owns the native UI (on Linux, the thread that
+/// will own the GTK/GDK display connection). GTK has thread affinity, so
+/// on Linux this must run right here rather than handing the dialog off
+/// to a worker thread — a worker thread iterating the main context would
+/// itself end up creating the GTK widgets on the wrong thread.
+pub fn fatal(title: &str, message: &str) -> ! {
+    show_blocking(title, message);
+    std::process::exit(1);
+}
+
+#[cfg(target_os = "linux")]
+fn show_blocking(title: &str, message: &str) {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let ctx = gtk::glib::MainContext::default();
+    let done = Rc::new(Cell::new(false));
+    let done_writer = done.clone();
+    let title = title.to_string();
+    let message = message.to_string();
+
+    // `invoke_local` (not `invoke`) because we're staying on this thread
+    // and the closure doesn't need to be `Send`.
+    ctx.invoke_local(move || {
+        tauri::api::dialog::blocking::MessageDialogBuilder::new(title, message)
+            .kind(tauri::api::dialog::MessageDialogKind::Error)
+            .show();
+        done_writer.set(true);
+    });
+
+    // `.run()` hasn't started pumping the glib main loop yet at this point
+    // in startup, so nothing else is driving this context — pump it
+    // ourselves, on this same (correct) thread, until our callback has run.
+    while !done.get() {
+        ctx.iteration(true);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn show_blocking(title: &str, message: &str) {
+    let title = title.to_string();
+    let message = message.to_string();
+
+    // macOS/Windows dialog APIs don't require the calling thread to be any
+    // particular thread, so a plain worker thread (joined so `fatal` still
+    // blocks until dismissed) is enough here.
+    let handle = thread::spawn(move || {
+        tauri::api::dialog::blocking::MessageDialogBuilder::new(title, message)
+            .kind(tauri::api::dialog::MessageDialogKind::Error)
+            .show();
+    });
+    let _ = handle.join();
+}
@@ -0,0 +1,202 @@
+//! Captures aetherd's stdout/stderr into a rotating log file so there's a
+//! record to attach to bug reports when it misbehaves.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{ChildStderr, ChildStdout};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const MAX_LOG_SIZE: u64 = 5 * 1024 * 1024;
+const MAX_ROTATED_FILES: usize = 5;
+const LOG_FILE_NAME: &str = "aetherd.log";
+
+/// A single log file that rolls over to `aetherd.log.1`, `.2`, ... once it
+/// crosses `MAX_LOG_SIZE`, keeping at most `MAX_ROTATED_FILES` old copies.
+/// Shared across restarts of the supervised child so the log is
+/// continuous rather than reset on every respawn.
+pub struct RotatingLog {
+    dir: PathBuf,
+    path: PathBuf,
+    file: Mutex<File>,
+    size: Mutex<u64>,
+}
+
+impl RotatingLog {
+    pub fn open(dir: &Path) -> std::io::Result<Arc<Self>> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(LOG_FILE_NAME);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Arc::new(RotatingLog {
+            dir: dir.to_path_buf(),
+            path,
+            file: Mutex::new(file),
+            size: Mutex::new(size),
+        }))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn append(&self, line: &str) {
+        let mut size = self.size.lock().unwrap();
+        if *size >= MAX_LOG_SIZE {
+            self.rotate(&mut size);
+        }
+
+        let mut file = self.file.lock().unwrap();
+        if file.write_all(line.as_bytes()).is_ok() {
+            *size += line.len() as u64;
+        }
+    }
+
+    fn rotate(&self, size: &mut u64) {
+        // If an earlier rotation already renamed the current file to `.1`
+        // but failed to open a fresh one, skip straight to the open below
+        // instead of shifting the rotated files again.
+        let renamed_away = !self.path.exists() && rotated_path(&self.path, 1).exists();
+        if !renamed_away {
+            for i in (1..MAX_ROTATED_FILES).rev() {
+                let _ = fs::rename(rotated_path(&self.path, i), rotated_path(&self.path, i + 1));
+            }
+            if fs::rename(&self.path, rotated_path(&self.path, 1)).is_err() {
+                // Couldn't roll the current file out of the way (e.g. held
+                // open elsewhere) — keep appending to it and retry rotation
+                // once it's grown past the threshold again.
+                return;
+            }
+        }
+
+        match OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.path)
+        {
+            Ok(file) => {
+                *self.file.lock().unwrap() = file;
+                *size = 0;
+            }
+            // The old file was renamed away but we couldn't open a fresh
+            // one; leave `size` alone so the next append() retries.
+            Err(_) => {}
+        }
+    }
+}
+
+fn rotated_path(base: &Path, n: usize) -> PathBuf {
+    let mut name = base.as_os_str().to_os_string();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+/// Spawns reader threads that copy `stdout`/`stderr` into `log`, prefixed
+/// by stream, until the pipes close — i.e. until the child exits, which
+/// naturally stops the threads before the next restart attempt.
+pub fn capture(stdout: ChildStdout, stderr: ChildStderr, log: Arc<RotatingLog>) {
+    let out_log = log.clone();
+    thread::spawn(move || drain(stdout, "out", &out_log));
+
+    thread::spawn(move || drain(stderr, "err", &log));
+}
+
+fn drain(stream: impl std::io::Read, tag: &str, log: &RotatingLog) {
+    let reader = BufReader::new(stream);
+    for line in reader.lines().map_while(Result::ok) {
+        log.append(&format!("[{tag}] {line}\n"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_log_dir(case: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "aether-rea-logging-test-{case}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn append_writes_lines_and_tracks_size() {
+        let dir = temp_log_dir("append");
+        let log = RotatingLog::open(&dir).unwrap();
+
+        log.append("hello\n");
+        log.append("world\n");
+
+        let contents = fs::read_to_string(dir.join(LOG_FILE_NAME)).unwrap();
+        assert_eq!(contents, "hello\nworld\n");
+        assert_eq!(*log.size.lock().unwrap(), contents.len() as u64);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rotate_shifts_the_current_file_to_dot_one_and_resets_size() {
+        let dir = temp_log_dir("rotate");
+        let log = RotatingLog::open(&dir).unwrap();
+
+        let line = "x".repeat(1024) + "\n";
+        let iterations = (MAX_LOG_SIZE as usize / line.len()) + 2;
+        for _ in 0..iterations {
+            log.append(&line);
+        }
+
+        assert!(dir.join(format!("{LOG_FILE_NAME}.1")).exists());
+        assert!(*log.size.lock().unwrap() < MAX_LOG_SIZE);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rotate_keeps_only_the_configured_number_of_rotated_files() {
+        let dir = temp_log_dir("rotate-cap");
+        let log = RotatingLog::open(&dir).unwrap();
+
+        let line = "x".repeat(1024) + "\n";
+        let iterations = (MAX_LOG_SIZE as usize / line.len()) + 2;
+        for _ in 0..(MAX_ROTATED_FILES + 2) {
+            for _ in 0..iterations {
+                log.append(&line);
+            }
+        }
+
+        assert!(!dir
+            .join(format!("{LOG_FILE_NAME}.{}", MAX_ROTATED_FILES + 1))
+            .exists());
+        assert!(dir
+            .join(format!("{LOG_FILE_NAME}.{MAX_ROTATED_FILES}"))
+            .exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn rotate_keeps_appending_without_losing_data_if_rename_fails() {
+        let dir = temp_log_dir("rotate-fail");
+        let log = RotatingLog::open(&dir).unwrap();
+
+        // Pull the directory out from under the open log file so the
+        // rename inside `rotate()` has nothing to rename onto and fails;
+        // on Unix the already-open file descriptor keeps working.
+        fs::remove_dir_all(&dir).unwrap();
+
+        let line = "x".repeat(1024) + "\n";
+        let iterations = (MAX_LOG_SIZE as usize / line.len()) + 2;
+        for _ in 0..iterations {
+            log.append(&line);
+        }
+
+        // A failed rotation must not reset the tracked size, or growth
+        // past MAX_LOG_SIZE would go undetected on every later append.
+        assert!(*log.size.lock().unwrap() >= MAX_LOG_SIZE);
+    }
+}
@@ -1,19 +1,86 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::process::{Command, Child};
+mod autostart;
+mod core_process;
+mod dialog;
+mod logging;
+mod shutdown;
+
+use std::process::{Command, Stdio};
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
-use std::sync::Mutex;
-use tauri::{CustomMenuItem, SystemTray, SystemTrayMenu, SystemTrayEvent, Manager};
+use std::sync::Arc;
+use tauri::{AppHandle, CustomMenuItem, SystemTray, SystemTrayMenu, SystemTrayEvent, Manager};
+
+use autostart::AutoStart;
+use core_process::{CoreAddr, CoreSupervisor};
+use logging::RotatingLog;
+
+fn spawn_aetherd(
+    app: &tauri::AppHandle,
+    api_addr: std::net::SocketAddr,
+    log: Arc<RotatingLog>,
+) -> std::io::Result<std::process::Child> {
+    let core_path = app
+        .path_resolver()
+        .resolve_resource("bin/aetherd")
+        .or_else(|| app.path_resolver().resolve_resource("bin/aetherd.exe"))
+        .unwrap_or_else(|| {
+            dialog::fatal(
+                "Aether 启动失败",
+                "未找到内置核心程序 aetherd，安装可能已损坏，请重新安装。",
+            )
+        });
+
+    let mut cmd = Command::new(core_path);
+    cmd.arg("--api")
+        .arg(api_addr.to_string())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    #[cfg(windows)]
+    {
+        // CREATE_NO_WINDOW = 0x08000000, CREATE_NEW_PROCESS_GROUP = 0x00000200
+        // (the latter so a CTRL_BREAK on shutdown only reaches aetherd, not us)
+        cmd.creation_flags(0x08000000 | 0x00000200);
+    }
+
+    let mut child = cmd.spawn()?;
+    let stdout = child.stdout.take().expect("stdout was piped");
+    let stderr = child.stderr.take().expect("stderr was piped");
+    logging::capture(stdout, stderr, log);
 
-struct CoreProcess(Mutex<Option<Child>>);
+    Ok(child)
+}
+
+/// Stops the supervisor and brings `aetherd` down cleanly. Shared by the
+/// tray "quit" handler and the app-exit path so shutdown is consistent no
+/// matter how the app is closed.
+fn shutdown_core(app: &AppHandle) {
+    let supervisor = app.state::<Arc<CoreSupervisor>>();
+    supervisor.begin_shutdown();
+    if let Some(mut child) = supervisor.take_child() {
+        let api_addr = app.state::<CoreAddr>().0;
+        shutdown::graceful_shutdown(&mut child, api_addr, shutdown::shutdown_timeout());
+    }
+}
 
 fn main() {
     let quit = CustomMenuItem::new("quit".to_string(), "退出");
     let hide = CustomMenuItem::new("hide".to_string(), "隐藏");
+    // `.selected()` forces this item to be created as a checkable/checkbox
+    // menu entry on every platform; the real checked state is synced right
+    // after `AutoStart` is set up in `setup()` below. Some tray backends
+    // only honor a later `set_selected()` call if the item was created as
+    // a checkbox to begin with.
+    let autostart = CustomMenuItem::new("autostart".to_string(), "开机自启").selected();
+    let open_logs = CustomMenuItem::new("open_logs".to_string(), "打开日志目录");
     let tray_menu = SystemTrayMenu::new()
+        .add_item(autostart)
+        .add_native_item(tauri::SystemTrayMenuItem::Separator)
         .add_item(hide)
+        .add_item(open_logs)
         .add_native_item(tauri::SystemTrayMenuItem::Separator)
         .add_item(quit);
 
@@ -28,28 +95,68 @@ fn main() {
             window.unminimize().unwrap();
             window.set_focus().unwrap();
         }))
-        .manage(CoreProcess(Mutex::new(None)))
+        .invoke_handler(tauri::generate_handler![
+            core_process::core_status,
+            core_process::core_api_addr
+        ])
         .setup(|app| {
-            // Start embedded aetherd
-            let core_path = app.path_resolver()
-                .resolve_resource("bin/aetherd")
-                .or_else(|| app.path_resolver().resolve_resource("bin/aetherd.exe"))
-                .expect("failed to resolve aetherd binary");
-            
-            let mut cmd = Command::new(core_path);
-            cmd.arg("--api").arg("127.0.0.1:9880");
-
-            #[cfg(windows)]
-            {
-                // CREATE_NO_WINDOW = 0x08000000
-                cmd.creation_flags(0x08000000);
+            // Start embedded aetherd, supervised so it gets restarted if it
+            // ever crashes unexpectedly.
+            let api_addr = core_process::pick_api_addr().unwrap_or_else(|err| {
+                dialog::fatal(
+                    "Aether 启动失败",
+                    &format!("无法为核心程序分配可用端口：{err}"),
+                )
+            });
+
+            let log_dir = app
+                .path_resolver()
+                .app_log_dir()
+                .unwrap_or_else(std::env::temp_dir);
+            let log = logging::RotatingLog::open(&log_dir).unwrap_or_else(|err| {
+                dialog::fatal(
+                    "Aether 启动失败",
+                    &format!("无法创建日志目录 {}：{err}", log_dir.display()),
+                )
+            });
+
+            let handle = app.handle();
+            let log_for_spawn = log.clone();
+            let supervisor = CoreSupervisor::spawn(handle.clone(), move || {
+                spawn_aetherd(&handle, api_addr, log_for_spawn.clone())
+            })
+            .unwrap_or_else(|err| {
+                dialog::fatal(
+                    "Aether 启动失败",
+                    &format!("核心程序 aetherd 启动失败：{err}"),
+                )
+            });
+
+            app.manage(supervisor);
+            app.manage(core_process::CoreAddr(api_addr));
+            app.manage(log);
+
+            // Autostart is a convenience, not something the rest of the app
+            // depends on; if we can't resolve our own exe path (deleted or
+            // moved binary, some sandboxes) just skip it instead of taking
+            // down startup over it.
+            match AutoStart::new(&app.handle()) {
+                Ok(auto_start) => {
+                    auto_start.sync_on_startup();
+                    if let Err(err) = app
+                        .tray_handle()
+                        .get_item("autostart")
+                        .set_selected(auto_start.is_enabled())
+                    {
+                        eprintln!("failed to sync autostart tray item state: {err}");
+                    }
+                    app.manage(auto_start);
+                }
+                Err(err) => {
+                    eprintln!("autostart unavailable, skipping: {err}");
+                }
             }
-            
-            let child = cmd.spawn()
-                .expect("failed to start aetherd");
-            
-            *app.state::<CoreProcess>().0.lock().unwrap() = Some(child);
-            
+
             Ok(())
         })
         .system_tray(system_tray)
@@ -61,16 +168,26 @@ fn main() {
             }
             SystemTrayEvent::MenuItemClick { id, .. } => match id.as_str() {
                 "quit" => {
-                    // Kill core process
-                    if let Some(mut child) = app.state::<CoreProcess>().0.lock().unwrap().take() {
-                        let _ = child.kill();
-                    }
+                    shutdown_core(app);
                     std::process::exit(0);
                 }
                 "hide" => {
                     let window = app.get_window("main").unwrap();
                     window.hide().unwrap();
                 }
+                "autostart" => {
+                    // Not managed if `AutoStart::new` failed during setup.
+                    if let Some(auto_start) = app.try_state::<AutoStart>() {
+                        let enabled = !auto_start.is_enabled();
+                        if auto_start.set_enabled(enabled).is_ok() {
+                            let _ = app.tray_handle().get_item("autostart").set_selected(enabled);
+                        }
+                    }
+                }
+                "open_logs" => {
+                    let log_dir = app.state::<Arc<RotatingLog>>().dir().to_path_buf();
+                    let _ = tauri::api::shell::open(&app.shell_scope(), log_dir.to_string_lossy(), None);
+                }
                 _ => {}
             },
             _ => {}
@@ -83,6 +200,11 @@ fn main() {
             }
             _ => {}
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                shutdown_core(app);
+            }
+        });
 }
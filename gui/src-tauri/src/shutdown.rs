@@ -0,0 +1,135 @@
+//! Graceful shutdown of the embedded `aetherd` child: give it a chance to
+//! flush state and close its API socket before falling back to `kill()`.
+
+use std::net::{SocketAddr, TcpStream};
+use std::process::Child;
+use std::time::{Duration, Instant};
+use std::thread;
+use std::io::Write;
+
+/// Default time to wait for `aetherd` to exit on its own before giving up
+/// and killing it, unless overridden by `AETHER_SHUTDOWN_TIMEOUT_MS`.
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Env var letting power users override [`DEFAULT_SHUTDOWN_TIMEOUT`],
+/// mirroring the `AETHER_API_PORT` override in `core_process.rs`.
+const SHUTDOWN_TIMEOUT_ENV: &str = "AETHER_SHUTDOWN_TIMEOUT_MS";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The timeout to pass to [`graceful_shutdown`]: `AETHER_SHUTDOWN_TIMEOUT_MS`
+/// if set to a valid number of milliseconds, otherwise
+/// [`DEFAULT_SHUTDOWN_TIMEOUT`].
+pub fn shutdown_timeout() -> Duration {
+    std::env::var(SHUTDOWN_TIMEOUT_ENV)
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT)
+}
+
+/// Attempts an orderly stop of `child`: a best-effort shutdown request to
+/// its `--api` endpoint plus a terminate signal, then polls `try_wait` for
+/// up to `timeout` before falling back to a hard `kill()`.
+pub fn graceful_shutdown(child: &mut Child, api_addr: SocketAddr, timeout: Duration) {
+    request_api_shutdown(api_addr);
+    send_terminate(child);
+
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        match child.try_wait() {
+            Ok(Some(_)) => return,
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(_) => return,
+        }
+    }
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Best-effort `POST /shutdown` to aetherd's own API so it can close its
+/// socket and flush state cleanly; any failure just falls through to the
+/// signal-based terminate below.
+fn request_api_shutdown(api_addr: SocketAddr) {
+    let Ok(mut stream) = TcpStream::connect_timeout(&api_addr, Duration::from_millis(500)) else {
+        return;
+    };
+    let request = format!(
+        "POST /shutdown HTTP/1.1\r\nHost: {api_addr}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+    );
+    let _ = stream.write_all(request.as_bytes());
+}
+
+#[cfg(unix)]
+fn send_terminate(child: &Child) {
+    unsafe {
+        libc::kill(child.id() as libc::pid_t, libc::SIGTERM);
+    }
+}
+
+#[cfg(windows)]
+fn send_terminate(child: &Child) {
+    use windows_sys::Win32::System::Console::{GenerateConsoleCtrlEvent, CTRL_BREAK_EVENT};
+
+    unsafe {
+        // Requires the child to have been spawned with
+        // CREATE_NEW_PROCESS_GROUP so this doesn't also signal us.
+        GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, child.id());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    // Nothing should be listening here; `request_api_shutdown` is expected
+    // to fail fast and fall through to the signal-based terminate.
+    fn unreachable_api_addr() -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], 1))
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn graceful_shutdown_returns_promptly_once_terminate_takes_effect() {
+        // No TERM handler, so the default disposition (exit) applies and
+        // the poll loop should pick that up well before the timeout.
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg("sleep 30")
+            .spawn()
+            .expect("spawn sleep");
+
+        let started = Instant::now();
+        graceful_shutdown(&mut child, unreachable_api_addr(), Duration::from_secs(10));
+        let elapsed = started.elapsed();
+
+        assert!(
+            elapsed < Duration::from_secs(5),
+            "expected terminate to end the child well before the 10s timeout, took {elapsed:?}"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn graceful_shutdown_falls_back_to_kill_if_the_child_ignores_terminate() {
+        // Ignoring TERM forces graceful_shutdown to ride out the full
+        // timeout and fall back to kill().
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg("trap '' TERM; sleep 30")
+            .spawn()
+            .expect("spawn sleep with TERM ignored");
+
+        let timeout = Duration::from_millis(300);
+        let started = Instant::now();
+        graceful_shutdown(&mut child, unreachable_api_addr(), timeout);
+        let elapsed = started.elapsed();
+
+        assert!(
+            elapsed >= timeout,
+            "expected the full timeout to elapse before falling back to kill(), took {elapsed:?}"
+        );
+    }
+}